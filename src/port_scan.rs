@@ -1,16 +1,105 @@
 use crate::PortScanPlugin;
 use nu_plugin::{EngineInterface, EvaluatedCall, PluginCommand};
 use nu_protocol::{
-    record, Category, Example, LabeledError, PipelineData, ShellError, Signature, Span,
-    SyntaxShape, Value,
+    record, Category, Example, LabeledError, ListStream, PipelineData, Range, ShellError, Signals,
+    Signature, Span, SyntaxShape, Value,
 };
-use std::io::{ Read, Write};
-use std::net::{SocketAddr, TcpStream};
+use std::collections::VecDeque;
+use std::io::{ ErrorKind, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 use std::vec;
 
 const DEFAULT_TIMEOUT: i64 = 60000000000;
 const TIME_MULTIPLIER: i64 = 1000000;
+const DEFAULT_UDP_BUFFER_SIZE: usize = 512;
+/// RFC 6555 "Happy Eyeballs" stagger between launching successive connection
+/// attempts, so a blackholed family doesn't make us wait out its full timeout
+/// before a working one gets a chance to answer.
+const HAPPY_EYEBALLS_STAGGER: Duration = Duration::from_millis(250);
+/// Default number of probes to run at once when `port`/`target IP` expand to
+/// more than one job and `--parallel` isn't given.
+const DEFAULT_PARALLELISM: usize = 10;
+/// Size of the one-shot read used to capture a banner when `--banner` is set
+/// without a `receive-byte-count`.
+const DEFAULT_BANNER_BUFFER_SIZE: usize = 4096;
+/// Ports only span 0..=65535, so no legitimate scan needs a `port` range
+/// that expands to more jobs than that; this also keeps an unbounded or
+/// absurdly large range from hanging/OOMing the plugin.
+const MAX_EXPANDED_PORTS: i64 = 65536;
+/// Upper bound on `targets.len() * ports.len()` - the total number of probe
+/// jobs a single invocation can expand to, regardless of whether the blowup
+/// came from a range, a list, or the cross product of both.
+const MAX_JOB_COUNT: usize = 65536;
+
+/// Flags shared by every probe in a scan, parsed once up front rather than
+/// per job.
+struct ScanFlags {
+    protocol: Protocol,
+    /// Deadline for the TCP handshake (`--connect-timeout`).
+    connect_duration: Duration,
+    /// Deadline for reads/writes once connected (`--timeout`).
+    io_duration: Duration,
+    send_data: Arc<Option<Vec<u8>>>,
+    receive_count: u64,
+    parallelism: usize,
+    capture_banner: bool,
+    shutdown_after_send: bool,
+    echo: bool,
+}
+
+/// Which optional fields `success_record` should add to a probe's record,
+/// carried as a struct rather than trailing bools so the call site can't
+/// silently transpose them.
+struct RecordOptions {
+    capture_banner: bool,
+    echo: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanResult {
+    Open,
+    Closed,
+    OpenFiltered,
+}
+
+impl ScanResult {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ScanResult::Open => "Open",
+            ScanResult::Closed => "Closed",
+            ScanResult::OpenFiltered => "Open|Filtered",
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(self, ScanResult::Open)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl Protocol {
+    fn from_flag(call: &EvaluatedCall) -> Result<Protocol, LabeledError> {
+        match call.get_flag_value("protocol") {
+            Some(Value::String { val, .. }) => match val.to_lowercase().as_str() {
+                "tcp" => Ok(Protocol::Tcp),
+                "udp" => Ok(Protocol::Udp),
+                other => Err(LabeledError::new(format!(
+                    "unknown protocol `{}`, expected `tcp` or `udp`",
+                    other
+                ))),
+            },
+            _ => Ok(Protocol::Tcp),
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct PortScan {}
@@ -22,15 +111,18 @@ impl PortScan {
 }
 
 impl PortScan {
-    fn scan(
-        call: &EvaluatedCall,
-        target_address: SocketAddr,
-    ) -> Result<(bool, u128), LabeledError> {
+    fn parse_flags(call: &EvaluatedCall) -> Result<ScanFlags, LabeledError> {
+        let protocol = Protocol::from_flag(call)?;
         let timeout: i64 = match call.get_flag_value("timeout") {
             Some(duration) => duration.as_duration().unwrap_or_else(|_| DEFAULT_TIMEOUT),
             None => DEFAULT_TIMEOUT,
         };
-        let duration = Duration::from_nanos(timeout.unsigned_abs());
+        let io_duration = Duration::from_nanos(timeout.unsigned_abs());
+        let connect_timeout: i64 = match call.get_flag_value("connect-timeout") {
+            Some(duration) => duration.as_duration().unwrap_or_else(|_| DEFAULT_TIMEOUT),
+            None => DEFAULT_TIMEOUT,
+        };
+        let connect_duration = Duration::from_nanos(connect_timeout.unsigned_abs());
         let send_data = match call.get_flag_value("send"){
             Some(Value::String { val,.. }) => {
                 Some(val.chars().map(|i|i as u8).collect())
@@ -43,53 +135,461 @@ impl PortScan {
             }
             _=> 0
         };
-        let now = Instant::now();
-        let is_open = Self::check_connection(target_address, duration,send_data,receive_count);
-        let elapsed = now.elapsed().as_nanos();
+        let parallelism = match call.get_flag_value("parallel") {
+            Some(Value::Int { val, .. }) if val > 0 => val as usize,
+            _ => DEFAULT_PARALLELISM,
+        };
+        let capture_banner = call.has_flag("banner").unwrap_or(false);
+        let shutdown_after_send = call.has_flag("shutdown-after-send").unwrap_or(false);
+        let echo = call.has_flag("echo").unwrap_or(false);
 
-        Ok((is_open, elapsed))
+        Ok(ScanFlags {
+            protocol,
+            connect_duration,
+            io_duration,
+            send_data: Arc::new(send_data),
+            receive_count,
+            parallelism,
+            capture_banner,
+            shutdown_after_send,
+            echo,
+        })
     }
-    fn check_connection(
-        address: SocketAddr, 
-        duration: Duration,
-        send_data: Option<Vec<u8>>,
-        receive_byte_count: u64,
-    ) -> bool {
-        match TcpStream::connect_timeout(&address, duration) {
-            Ok(mut stream) =>{
-                // eprintln!("Begin sending data");
-                if let Some(data )= send_data{
-                    if let Err(err)=  stream.write_all(&data) {
-                        eprintln!("Error writing to socket stream, {}", err);
-                        return false;
+
+    /// Returns `(result, connect_elapsed, io_elapsed, connected_address, banner)`.
+    /// `connect_elapsed` times establishing the socket (the TCP handshake, or
+    /// the UDP bind+connect); `io_elapsed` times everything after (send,
+    /// optional half-close, read, final shutdown).
+    fn scan(
+        flags: &ScanFlags,
+        candidates: &[SocketAddr],
+    ) -> (ScanResult, u128, u128, SocketAddr, Option<Vec<u8>>) {
+        // Borrowed, not cloned: every probe shares the one `--send` payload
+        // rather than deep-copying it per job.
+        let send_data: Option<&[u8]> = flags.send_data.as_deref();
+        // `--echo` needs the reply back regardless of whether `--banner` was
+        // also passed, since it's parsed into `public_address` downstream.
+        let want_payload = flags.capture_banner || flags.echo;
+        match flags.protocol {
+            Protocol::Tcp => {
+                let connect_start = Instant::now();
+                let connected = Self::connect_happy_eyeballs(candidates, flags.connect_duration);
+                let connect_elapsed = connect_start.elapsed().as_nanos();
+                match connected {
+                    Ok((stream, address)) => {
+                        let io_start = Instant::now();
+                        let (result, banner) = Self::check_connection(
+                            stream,
+                            flags.io_duration,
+                            send_data,
+                            flags.receive_count,
+                            want_payload,
+                            flags.shutdown_after_send,
+                        );
+                        let io_elapsed = io_start.elapsed().as_nanos();
+                        (result, connect_elapsed, io_elapsed, address, banner)
                     }
-                    // else{
-                    //      eprintln!("no error sending data");
-                    // }
+                    Err(_) => (ScanResult::Closed, connect_elapsed, 0, candidates[0], None),
                 }
-                // stream.flush().unwrap();
-                // eprintln!("After send data");
-                if let Err(err) = stream.set_read_timeout(Some(duration)){
-                    eprintln!("Error setting read timeout, {}", err);
-                    return false;
-                }
-                
-                if receive_byte_count!=0 {
-                    // eprintln!("Wait to read the amount of bytes requested");
-                    let buffer : Result<Vec<u8>,std::io::Error>=stream.bytes().take(receive_byte_count as usize).collect();
-                    let result= match buffer{
-                        Ok(_) =>{
-                            // eprintln!("Data received: {:?}", data);
-                            true
-                        },
-                        Err(err) => {eprintln!("Error reading from socket stream, {}", err); false}
-                    };
-                    return result
-                    
+            }
+            Protocol::Udp => {
+                let address = candidates[0];
+                let connect_start = Instant::now();
+                let socket = Self::connect_udp(address);
+                let connect_elapsed = connect_start.elapsed().as_nanos();
+                match socket {
+                    Ok(socket) => {
+                        let io_start = Instant::now();
+                        let (result, banner) = Self::check_udp_connection(
+                            socket,
+                            flags.io_duration,
+                            send_data,
+                            flags.receive_count,
+                            want_payload,
+                        );
+                        let io_elapsed = io_start.elapsed().as_nanos();
+                        (result, connect_elapsed, io_elapsed, address, banner)
+                    }
+                    Err(err) => {
+                        eprintln!("Error binding/connecting udp socket, {}", err);
+                        (ScanResult::Closed, connect_elapsed, 0, address, None)
+                    }
                 }
-                true
+            }
+        }
+    }
+
+    /// Resolves `target:port`, races the candidates and produces the output
+    /// record for a single probe. Failures (bad port, unresolvable
+    /// hostname, ...) are reported as part of the record rather than as a
+    /// hard error, since one bad entry in a list/range of jobs shouldn't
+    /// abort the rest of the stream.
+    fn probe(
+        flags: &ScanFlags,
+        head: Span,
+        target: &str,
+        target_span: Span,
+        port: i64,
+        port_span: Span,
+    ) -> Value {
+        let port_u16 = match u16::try_from(port) {
+            Ok(port) => port,
+            Err(_) => {
+                return Self::error_record(
+                    head,
+                    target,
+                    target_span,
+                    port,
+                    port_span,
+                    format!("port `{}` is out of the valid 0-65535 range", port),
+                    flags,
+                )
+            }
+        };
+        let resolved = match (target, port_u16).to_socket_addrs() {
+            Ok(addresses) => addresses.collect::<Vec<SocketAddr>>(),
+            Err(err) => {
+                return Self::error_record(
+                    head,
+                    target,
+                    target_span,
+                    port,
+                    port_span,
+                    format!("failed to resolve `{}:{}`: {}", target, port, err),
+                    flags,
+                )
+            }
+        };
+        if resolved.is_empty() {
+            return Self::error_record(
+                head,
+                target,
+                target_span,
+                port,
+                port_span,
+                format!("`{}` did not resolve to any address", target),
+                flags,
+            );
+        }
+        let candidates = Self::happy_eyeballs_order(resolved);
+        let (result, connect_elapsed, io_elapsed, connected_address, banner) =
+            Self::scan(flags, &candidates);
+        let connect_elapsed: i64 = connect_elapsed.try_into().unwrap_or(0);
+        let io_elapsed: i64 = io_elapsed.try_into().unwrap_or(0);
+
+        Self::success_record(
+            head,
+            target,
+            target_span,
+            port,
+            port_span,
+            result,
+            connect_elapsed,
+            io_elapsed,
+            connected_address,
+            banner,
+            RecordOptions {
+                capture_banner: flags.capture_banner,
+                echo: flags.echo,
             },
-            Err(_) => false,
+        )
+    }
+
+    fn success_record(
+        head: Span,
+        target: &str,
+        target_span: Span,
+        port: i64,
+        port_span: Span,
+        result: ScanResult,
+        connect_elapsed: i64,
+        io_elapsed: i64,
+        connected_address: SocketAddr,
+        banner: Option<Vec<u8>>,
+        options: RecordOptions,
+    ) -> Value {
+        let mut fields = record! {
+            "address" => Value::string(target, target_span),
+            "port" => Value::int(port, port_span),
+            "result" => Value::string(result.as_str(), head),
+            "is_open" => Value::bool(result.is_open(), head),
+            "elapsed" => Value::duration(connect_elapsed.saturating_add(io_elapsed), head),
+            "connect_elapsed" => Value::duration(connect_elapsed, head),
+            "io_elapsed" => Value::duration(io_elapsed, head),
+            "connected_address" => Value::string(connected_address.to_string(), head),
+            "family" => Value::string(
+                if connected_address.is_ipv6() { "IPv6" } else { "IPv4" },
+                head,
+            ),
+        };
+        if options.echo {
+            let public_address = banner
+                .as_deref()
+                .and_then(Self::parse_echo_payload)
+                .map(|addr| addr.to_string())
+                .unwrap_or_default();
+            fields.push("public_address", Value::string(public_address, head));
+        }
+        if options.capture_banner {
+            fields.push("banner", Value::binary(banner.unwrap_or_default(), head));
+        }
+
+        Value::record(fields, head)
+    }
+
+    /// Recovers a peer-observed address from an echo server's reply.
+    /// "What's my IP" style services typically answer with a bare `ip` or
+    /// `ip:port` as plain text, so this takes the first whitespace-separated
+    /// token that parses as either.
+    fn parse_echo_payload(payload: &[u8]) -> Option<SocketAddr> {
+        let text = std::str::from_utf8(payload).ok()?;
+        text.split_whitespace().find_map(|token| {
+            let token = token.trim_matches(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == ':' || c == '[' || c == ']'));
+            token
+                .parse::<SocketAddr>()
+                .ok()
+                .or_else(|| token.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, 0)))
+        })
+    }
+
+    /// Builds an error row with the same column set `success_record` would
+    /// have produced for the same flags plus an `error` column (the fields
+    /// that depend on a connection having been attempted are zero/empty), so a
+    /// batch scan doesn't stream rows with a different shape depending on
+    /// whether resolution failed.
+    fn error_record(
+        head: Span,
+        target: &str,
+        target_span: Span,
+        port: i64,
+        port_span: Span,
+        error: String,
+        flags: &ScanFlags,
+    ) -> Value {
+        let mut fields = record! {
+            "address" => Value::string(target, target_span),
+            "port" => Value::int(port, port_span),
+            "result" => Value::string("Error", head),
+            "is_open" => Value::bool(false, head),
+            "elapsed" => Value::duration(0, head),
+            "connect_elapsed" => Value::duration(0, head),
+            "io_elapsed" => Value::duration(0, head),
+            "connected_address" => Value::string("", head),
+            "family" => Value::string("", head),
+            "error" => Value::string(error, head),
+        };
+        if flags.echo {
+            fields.push("public_address", Value::string("", head));
+        }
+        if flags.capture_banner {
+            fields.push("banner", Value::binary(Vec::new(), head));
+        }
+
+        Value::record(fields, head)
+    }
+
+    /// Races `TcpStream::connect_timeout` against every candidate address,
+    /// launching attempts [`HAPPY_EYEBALLS_STAGGER`] apart (RFC 6555) instead
+    /// of waiting for each one to fully time out in turn. The first socket to
+    /// connect wins; the rest are left to finish or time out on their own
+    /// threads and are dropped.
+    fn connect_happy_eyeballs(
+        candidates: &[SocketAddr],
+        duration: Duration,
+    ) -> Result<(TcpStream, SocketAddr), std::io::Error> {
+        let (tx, rx) = mpsc::channel();
+        for (index, address) in candidates.iter().enumerate() {
+            let tx = tx.clone();
+            let address = *address;
+            let delay = HAPPY_EYEBALLS_STAGGER * index as u32;
+            thread::spawn(move || {
+                if !delay.is_zero() {
+                    thread::sleep(delay);
+                }
+                let result = TcpStream::connect_timeout(&address, duration);
+                let _ = tx.send((address, result));
+            });
+        }
+        drop(tx);
+
+        let mut last_err = None;
+        for _ in 0..candidates.len() {
+            match rx.recv() {
+                Ok((address, Ok(stream))) => return Ok((stream, address)),
+                Ok((_, Err(err))) => last_err = Some(err),
+                Err(_) => break,
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            std::io::Error::new(ErrorKind::TimedOut, "no candidate address connected")
+        }))
+    }
+
+    /// Orders resolved addresses the way RFC 6555 "Happy Eyeballs" expects:
+    /// interleaved by family, IPv6 first, so a dual-stack target tries both
+    /// families up front instead of exhausting one before touching the other.
+    fn happy_eyeballs_order(addresses: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        let mut v6: Vec<SocketAddr> = addresses.iter().copied().filter(|a| a.is_ipv6()).collect();
+        let mut v4: Vec<SocketAddr> = addresses.iter().copied().filter(|a| a.is_ipv4()).collect();
+        let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+        while !v6.is_empty() || !v4.is_empty() {
+            if !v6.is_empty() {
+                ordered.push(v6.remove(0));
+            }
+            if !v4.is_empty() {
+                ordered.push(v4.remove(0));
+            }
+        }
+        ordered
+    }
+
+    fn check_connection(
+        mut stream: TcpStream,
+        duration: Duration,
+        send_data: Option<&[u8]>,
+        receive_byte_count: u64,
+        capture_banner: bool,
+        shutdown_after_send: bool,
+    ) -> (ScanResult, Option<Vec<u8>>) {
+        if let Err(err) = stream.set_write_timeout(Some(duration)) {
+            eprintln!("Error setting write timeout, {}", err);
+            let _ = stream.shutdown(Shutdown::Both);
+            return (ScanResult::Closed, None);
+        }
+        if let Some(data) = send_data {
+            if let Err(err) = stream.write_all(data) {
+                eprintln!("Error writing to socket stream, {}", err);
+                let _ = stream.shutdown(Shutdown::Both);
+                return (ScanResult::Closed, None);
+            }
+            if shutdown_after_send {
+                // Some line protocols only reply once they see EOF on the
+                // write side, so give them the chance before we read back.
+                if let Err(err) = stream.shutdown(Shutdown::Write) {
+                    eprintln!("Error half-closing socket stream, {}", err);
+                }
+            }
+        }
+        if let Err(err) = stream.set_read_timeout(Some(duration)) {
+            eprintln!("Error setting read timeout, {}", err);
+            let _ = stream.shutdown(Shutdown::Both);
+            return (ScanResult::Closed, None);
+        }
+
+        let (result, banner) = if receive_byte_count != 0 {
+            let bytes = Self::read_up_to(&mut stream, receive_byte_count as usize);
+            if bytes.is_empty() {
+                (ScanResult::Closed, None)
+            } else {
+                (ScanResult::Open, capture_banner.then_some(bytes))
+            }
+        } else if capture_banner {
+            let banner = Self::read_banner_best_effort(&mut stream, DEFAULT_BANNER_BUFFER_SIZE);
+            (ScanResult::Open, Some(banner))
+        } else {
+            (ScanResult::Open, None)
+        };
+
+        let _ = stream.shutdown(Shutdown::Both);
+        (result, banner)
+    }
+
+    /// Captures whatever the peer has already sent (or sends before the read
+    /// timeout elapses) without requiring an exact byte count - useful for
+    /// greeting banners (SSH, SMTP, ...) that arrive unprompted.
+    fn read_banner_best_effort(stream: &mut TcpStream, max_bytes: usize) -> Vec<u8> {
+        let mut buffer = vec![0u8; max_bytes];
+        match stream.read(&mut buffer) {
+            Ok(read) => {
+                buffer.truncate(read);
+                buffer
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Reads up to `max_bytes` for `--receive-byte-count`, looping over
+    /// successive `read` calls since one call can return fewer bytes than
+    /// asked for. Whatever was read before a later call times out or errors
+    /// is kept rather than discarded, the same "best effort" contract
+    /// `read_banner_best_effort` already has.
+    fn read_up_to(stream: &mut TcpStream, max_bytes: usize) -> Vec<u8> {
+        let mut buffer = vec![0u8; max_bytes];
+        let mut filled = 0;
+        while filled < max_bytes {
+            match stream.read(&mut buffer[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(_) => break,
+            }
+        }
+        buffer.truncate(filled);
+        buffer
+    }
+
+    /// Binds and connects a UDP socket to `address`; this is the UDP
+    /// analogue of the TCP handshake and is timed separately from the
+    /// send/receive phase.
+    fn connect_udp(address: SocketAddr) -> Result<UdpSocket, std::io::Error> {
+        let bind_address = match address {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        let socket = UdpSocket::bind(bind_address)?;
+        socket.connect(address)?;
+        Ok(socket)
+    }
+
+    /// UDP has no handshake, so "open" can only be inferred from a reply (or
+    /// an ICMP port-unreachable coming back as a `ConnectionRefused`/`ConnectionReset`
+    /// error). Silence on a connectionless protocol is ambiguous - it can mean
+    /// "open, but the service doesn't answer unsolicited probes" just as easily
+    /// as "filtered" - so we report that case as `Open|Filtered` like classic
+    /// scanners do instead of guessing.
+    fn check_udp_connection(
+        socket: UdpSocket,
+        duration: Duration,
+        send_data: Option<&[u8]>,
+        receive_byte_count: u64,
+        capture_banner: bool,
+    ) -> (ScanResult, Option<Vec<u8>>) {
+        if let Some(data) = send_data {
+            if let Err(err) = socket.send(data) {
+                return (Self::classify_udp_io_error(&err), None);
+            }
+        }
+        if let Err(err) = socket.set_read_timeout(Some(duration)) {
+            eprintln!("Error setting read timeout, {}", err);
+            return (ScanResult::Closed, None);
+        }
+        let buffer_size = if receive_byte_count != 0 {
+            receive_byte_count as usize
+        } else {
+            DEFAULT_UDP_BUFFER_SIZE
+        };
+        let mut buffer = vec![0u8; buffer_size];
+        match socket.recv(&mut buffer) {
+            Ok(read) => {
+                let banner = if capture_banner {
+                    buffer.truncate(read);
+                    Some(buffer)
+                } else {
+                    None
+                };
+                (ScanResult::Open, banner)
+            }
+            Err(err) => (Self::classify_udp_io_error(&err), None),
+        }
+    }
+
+    fn classify_udp_io_error(err: &std::io::Error) -> ScanResult {
+        match err.kind() {
+            ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset => ScanResult::Closed,
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => ScanResult::OpenFiltered,
+            _ => {
+                eprintln!("Error on udp socket, {}", err);
+                ScanResult::OpenFiltered
+            }
         }
     }
 
@@ -116,6 +616,79 @@ impl PortScan {
         };
         Ok((target, port))
     }
+
+    /// Expands the `target IP` argument into one or more `(address, span)`
+    /// jobs - a bare string scans a single target, a list scans all of them.
+    fn expand_targets(value: &Value) -> Result<Vec<(String, Span)>, LabeledError> {
+        match value {
+            Value::List { vals, .. } => vals.iter().map(Self::target_string).collect(),
+            Value::String { .. } => Ok(vec![Self::target_string(value)?]),
+            other => Err(LabeledError::new("target IP must be a string or a list of strings")
+                .with_label("Target Address error", other.span())),
+        }
+    }
+
+    fn target_string(value: &Value) -> Result<(String, Span), LabeledError> {
+        match value.as_str() {
+            Ok(target) => Ok((target.to_string(), value.span())),
+            Err(err) => {
+                Err(LabeledError::new(err.to_string()).with_label("Target Address error", value.span()))
+            }
+        }
+    }
+
+    /// Expands the `port` argument into one or more `(port, span)` jobs - a
+    /// bare int scans a single port, a range or a list scans all of them.
+    fn expand_ports(value: &Value) -> Result<Vec<(i64, Span)>, LabeledError> {
+        match value {
+            Value::List { vals, .. } => vals.iter().map(Self::port_int).collect(),
+            Value::Int { .. } => Ok(vec![Self::port_int(value)?]),
+            Value::Range { val, .. } => match val.as_ref() {
+                Range::IntRange(range) => {
+                    let span = value.span();
+                    let end = match range.end() {
+                        std::ops::Bound::Included(end) => end,
+                        std::ops::Bound::Excluded(end) => end - 1,
+                        std::ops::Bound::Unbounded => {
+                            return Err(LabeledError::new("port range has no upper bound")
+                                .with_label(
+                                    "unbounded ranges would expand into an unlimited number of jobs; give the range an end, e.g. `7880..8000`",
+                                    span,
+                                ))
+                        }
+                    };
+                    let step = range.step().unsigned_abs().max(1);
+                    let span_len = end.saturating_sub(range.start()).unsigned_abs() / step + 1;
+                    if span_len > MAX_EXPANDED_PORTS.unsigned_abs() {
+                        return Err(LabeledError::new(format!(
+                            "port range expands to {} ports, more than the {} possible port numbers",
+                            span_len, MAX_EXPANDED_PORTS
+                        ))
+                        .with_label("Target Port error", span));
+                    }
+                    Ok(range
+                        .into_range_iter(Signals::empty())
+                        .map(|port| (port, span))
+                        .collect())
+                }
+                Range::FloatRange(_) => Err(LabeledError::new("port ranges must use integer bounds")
+                    .with_label("Target Port error", value.span())),
+            },
+            other => Err(
+                LabeledError::new("port must be an int, a range of ints, or a list of ints")
+                    .with_label("Target Port error", other.span()),
+            ),
+        }
+    }
+
+    fn port_int(value: &Value) -> Result<(i64, Span), LabeledError> {
+        match value.as_int() {
+            Ok(port) => Ok((port, value.span())),
+            Err(err) => {
+                Err(LabeledError::new(err.to_string()).with_label("Target Port error", value.span()))
+            }
+        }
+    }
 }
 
 impl PluginCommand for PortScan {
@@ -128,16 +701,26 @@ impl PluginCommand for PortScan {
         Signature::build("port scan")
             .required(
                 "target IP",
-                SyntaxShape::String,
-                "target IP address to check for open port",
+                SyntaxShape::Any,
+                "target IP address or hostname to check for open port, or a list of them",
+            )
+            .required(
+                "port",
+                SyntaxShape::Any,
+                "port to be checked, or a range/list of ports",
             )
-            .required("port", SyntaxShape::Int, "port to be checked")
             .named(
                 "timeout",
                 SyntaxShape::Duration,
-                "time before giving up the connection. (default: 60 Seconds)",
+                "time before giving up once connected, covering send/receive. (default: 60 Seconds)",
                 Some('t'),
             )
+            .named(
+                "connect-timeout",
+                SyntaxShape::Duration,
+                "time before giving up on establishing the connection itself. (default: 60 Seconds)",
+                Some('c'),
+            )
             .named(
                 "send",
                 SyntaxShape::String,
@@ -146,14 +729,41 @@ impl PluginCommand for PortScan {
             )
             .named(
                 "receive-byte-count",
-                 SyntaxShape::Int, 
-                 "bytes to receive from the target (possibly after sending the `send` data) to mark the connection as open", 
+                 SyntaxShape::Int,
+                 "bytes to receive from the target (possibly after sending the `send` data) to mark the connection as open",
                  Some('b'),
                 )
+            .named(
+                "protocol",
+                SyntaxShape::String,
+                "protocol to use for the probe, `tcp` or `udp` (default: tcp)",
+                Some('p'),
+            )
+            .named(
+                "parallel",
+                SyntaxShape::Int,
+                "maximum number of probes to run at once when `target IP`/`port` expand to more than one job (default: 10)",
+                Some('j'),
+            )
+            .switch(
+                "banner",
+                "capture whatever bytes are read (up to `receive-byte-count`, or until the read timeout) and add them to the output as `banner`",
+                Some('B'),
+            )
+            .switch(
+                "shutdown-after-send",
+                "half-close the write side of the connection right after `send` data is written, before waiting to read a reply (Tcp only)",
+                Some('H'),
+            )
+            .switch(
+                "echo",
+                "treat the target as a reachability/echo endpoint: read its reply and parse the peer-observed address out of it into `public_address`, confirming the port is reachable end-to-end rather than merely open",
+                Some('e'),
+            )
             .category(Category::Network)
     }
     fn description(&self) -> &str {
-        "The `port scan` command serves a similar purpose to the `nc -vz {ip} {port}` command,\nIt allows you to detect open ports on a target and provides valuable information about the connection time."
+        "The `port scan` command serves a similar purpose to the `nc -vz {ip} {port}` command,\nIt allows you to detect open ports on a target and provides valuable information about the connection time.\nBoth `tcp` (default) and `udp` probes are supported through the `--protocol` flag.\nHostnames are resolved through DNS and, for Tcp, dual-stack (IPv4/IPv6) targets are raced with a RFC 6555 \"Happy Eyeballs\" connection attempt so a blackholed family doesn't stall the scan.\nThe connect phase and the send/receive phase are timed and can be bounded separately through `--connect-timeout` and `--timeout`; `--shutdown-after-send` half-closes the write side right after `send` data goes out, for protocols that only reply once they see EOF.\n`--echo` treats the target as a reachability/echo endpoint: it reads back whatever the server replies with, parses out the peer-observed address, and reports it as `public_address`, using `connect_elapsed`/`io_elapsed` as the round-trip cost of reaching it.\n`target IP` and `port` each accept a single value, a list, or (for `port`) a range; every target/port combination is probed concurrently, bounded by `--parallel`, and results stream back as they complete."
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -169,6 +779,10 @@ impl PluginCommand for PortScan {
                                 "result" => Value::test_string("Open", ),
                                 "is_open"=> Value::test_bool(true, ),
                                 "elapsed" =>  Value::test_duration(40*TIME_MULTIPLIER),
+                                "connect_elapsed" =>  Value::test_duration(25*TIME_MULTIPLIER),
+                                "io_elapsed" =>  Value::test_duration(15*TIME_MULTIPLIER),
+                                "connected_address" => Value::test_string("8.8.8.8:53".to_string()),
+                                "family" => Value::test_string("IPv4".to_string()),
                             },
                         Span::unknown(),
                     )
@@ -185,74 +799,147 @@ impl PluginCommand for PortScan {
                                 "result" => Value::test_string("Closed", ),
                                 "is_open"=> Value::test_bool(false, ),
                                 "elapsed" =>  Value::test_duration(1000*TIME_MULTIPLIER),
+                                "connect_elapsed" =>  Value::test_duration(1000*TIME_MULTIPLIER),
+                                "io_elapsed" =>  Value::test_duration(0),
+                                "connected_address" => Value::test_string("8.8.8.8:54".to_string()),
+                                "family" => Value::test_string("IPv4".to_string()),
                             },
                         Span::unknown(),
                     )
                 ),
             },
             Example {
-                example: "7880..8000 | each { |it| port scan 127.0.0.1 $it -t 1ms } | where result == Open",
-                description: "This command will scan any port from 7880 to 8000 on localhost and return open ports in range",
+                example: "port scan 127.0.0.1 7880..8000 -t 1ms -j 50 | where result == Open",
+                description: "scans every port from 7880 to 8000 on localhost concurrently, up to 50 probes in flight at once, and returns open ports in range",
+                result: None,
+            },
+            Example {
+                example: "port scan [127.0.0.1, 10.0.0.1] [22, 80, 443] -j 20",
+                description: "scans every combination of the given targets and ports concurrently",
                 result: None,
             },
+            Example {
+                example: "port scan 8.8.8.8 53 -p udp -t 1sec",
+                description: "this will send a UDP probe to port 53 on 8.8.8.8; with no reply and no ICMP unreachable the port is reported as `Open|Filtered`",
+                result: Some(
+                    Value::record(
+                        record! {
+                                "address" => Value::test_string("8.8.8.8".to_string()),
+                                "port" => Value::test_int(53),
+                                "result" => Value::test_string("Open|Filtered", ),
+                                "is_open"=> Value::test_bool(false, ),
+                                "elapsed" =>  Value::test_duration(1000*TIME_MULTIPLIER),
+                                "connect_elapsed" =>  Value::test_duration(0),
+                                "io_elapsed" =>  Value::test_duration(1000*TIME_MULTIPLIER),
+                                "connected_address" => Value::test_string("8.8.8.8:53".to_string()),
+                                "family" => Value::test_string("IPv4".to_string()),
+                            },
+                        Span::unknown(),
+                    )
+                ),
+            },
+            Example {
+                example: "port scan dns.google 443 -t 1sec",
+                description: "hostnames are resolved through DNS; on a dual-stack target the IPv6 and IPv4 addresses race and `connected_address`/`family` report whichever answered first",
+                result: None,
+            },
+            Example {
+                example: "port scan example.com 80 -s \"HEAD / HTTP/1.0\\r\\n\\r\\n\" -B -b 256",
+                description: "sends a HEAD request and captures up to 256 bytes of the reply as `banner`, letting you inspect the `Server:` line without a second tool",
+                result: None,
+            },
+            Example {
+                example: "port scan example.com 80 -c 500ms -t 2sec -s \"HEAD / HTTP/1.0\\r\\n\\r\\n\" -H -B",
+                description: "caps the handshake itself to 500ms via `--connect-timeout`, then half-closes the write side right after the request is sent (`--shutdown-after-send`) so the server sees EOF before it has to reply",
+                result: None,
+            },
+            Example {
+                example: "port scan ip-echo.example.com 7 -e",
+                description: "connects to a reachability/echo endpoint and reports the externally-observed address it replied with, confirming the port is reachable end-to-end and not just open locally",
+                result: Some(
+                    Value::record(
+                        record! {
+                                "address" => Value::test_string("ip-echo.example.com".to_string()),
+                                "port" => Value::test_int(7),
+                                "result" => Value::test_string("Open", ),
+                                "is_open"=> Value::test_bool(true, ),
+                                "elapsed" =>  Value::test_duration(60*TIME_MULTIPLIER),
+                                "connect_elapsed" =>  Value::test_duration(30*TIME_MULTIPLIER),
+                                "io_elapsed" =>  Value::test_duration(30*TIME_MULTIPLIER),
+                                "connected_address" => Value::test_string("203.0.113.10:7".to_string()),
+                                "family" => Value::test_string("IPv4".to_string()),
+                                "public_address" => Value::test_string("198.51.100.42:54321".to_string()),
+                            },
+                        Span::unknown(),
+                    )
+                ),
+            },
         ]
     }
 
     fn run(
         &self,
         _plugin: &Self::Plugin,
-        _engine: &EngineInterface,
+        engine: &EngineInterface,
         call: &EvaluatedCall,
         _input: PipelineData,
     ) -> Result<PipelineData, LabeledError> {
+        let signals = engine.signals().clone();
         let (target, port) = match Self::extract_params(call) {
             Ok((target, port)) => (target, port),
             Err(e) => return Err(LabeledError::from(e)),
         };
 
-        let real_target = match target.as_str() {
-            Ok(real_target) => real_target,
-            Err(e) => {
-                return Err(LabeledError::new(e.to_string()).with_label("Target Address error", target.span()));
-            }
-        };
-        let real_port = match port.as_int() {
-            Ok(real_port) => real_port,
-            Err(e) => {
-                return Err(LabeledError::new(e.to_string()).with_label("Target Port error", port.span()));
-            }
-        };
-        let address = match format!("{}:{}", real_target, real_port).parse::<SocketAddr>() {
-            Ok(address) => address,
-            Err(err) => {
-                let span = Span::new(target.span().start, port.span().end);
-                return Err(LabeledError::new(format!(
-                    "as `{}:{}` got `{}`. note: do not use domain name in address.",
-                    real_target, real_port, err,
-                )).with_label("Address parser exception".to_string(),span));
-            }
-        };
-        let (is_open, elapsed) = match Self::scan(call, address) {
-            Ok(value) => value,
-            Err(value) => return Err(value),
-        };
-        let str_result = match is_open {
-            true => "Open",
-            false => "Closed",
-        };
-        let elapsed: i64 = elapsed.try_into().unwrap_or_else(|_| 0);
-
-        Ok(PipelineData::Value(
-            Value::record(
-                record! {
-                    "address" => nu_protocol::Value::string(real_target, target.span()),
-                    "port" => nu_protocol::Value::int(real_port, port.span()),
-                    "result" => nu_protocol::Value::string(str_result, call.head),
-                    "is_open"=> nu_protocol::Value::bool(is_open, call.head),
-                    "elapsed" =>  nu_protocol::Value::duration(elapsed, call.head),
-                },
+        let targets = Self::expand_targets(&target)?;
+        let ports = Self::expand_ports(&port)?;
+        let flags = Self::parse_flags(call)?;
+
+        let job_count = targets.len().saturating_mul(ports.len());
+        if job_count > MAX_JOB_COUNT {
+            return Err(LabeledError::new(format!(
+                "{} target(s) x {} port(s) would expand to {} jobs, more than the {} limit",
+                targets.len(),
+                ports.len(),
+                job_count,
+                MAX_JOB_COUNT
+            ))
+            .with_label(
+                "too many targets/ports to expand into jobs for a single invocation",
                 call.head,
-            ),
+            ));
+        }
+
+        let mut jobs = VecDeque::with_capacity(job_count);
+        for (target, target_span) in &targets {
+            for (port, port_span) in &ports {
+                jobs.push_back((target.clone(), *target_span, *port, *port_span));
+            }
+        }
+        let worker_count = flags.parallelism.min(jobs.len()).max(1);
+
+        let head = call.head;
+        let flags = Arc::new(flags);
+        let jobs = Arc::new(Mutex::new(jobs));
+        let (tx, rx) = mpsc::channel::<Value>();
+        for _ in 0..worker_count {
+            let jobs = Arc::clone(&jobs);
+            let flags = Arc::clone(&flags);
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                let job = jobs.lock().unwrap().pop_front();
+                let Some((target, target_span, port, port_span)) = job else {
+                    break;
+                };
+                let record = Self::probe(&flags, head, &target, target_span, port, port_span);
+                if tx.send(record).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        Ok(PipelineData::ListStream(
+            ListStream::new(rx.into_iter(), head, signals),
             None,
         ))
     }